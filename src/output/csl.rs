@@ -0,0 +1,621 @@
+//! A generic [CSL 1.0](https://docs.citationstyles.org/en/stable/specification.html)
+//! style engine.
+//!
+//! Unlike the hand-written [apa](super::apa), [chicago](super::chicago),
+//! [ieee](super::ieee) and [mla](super::mla) modules, this one parses a CSL XML
+//! style file into an in-memory AST once and then interprets that AST against
+//! an [Entry] whenever a reference or citation is requested. This lets callers
+//! drop in any of the thousands of existing CSL styles instead of waiting for a
+//! hand-written module.
+
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+use thiserror::Error;
+
+use super::{
+    AtomicCitation, BibliographyFormatter, CitationError, CitationFormatter,
+    DisplayString, Formatting,
+};
+use crate::types::{Date, Person};
+use crate::Entry;
+
+/// Raised when a CSL style file cannot be turned into a [CslStyle].
+#[derive(Debug, Error)]
+pub enum CslError {
+    /// The file was not well-formed XML.
+    #[error("the style is not valid XML: {0}")]
+    Xml(#[from] roxmltree::Error),
+    /// A required element was missing from the style.
+    #[error("the style is missing a <{0}> element")]
+    MissingElement(&'static str),
+}
+
+/// Affixes and inline formatting shared by every rendering element.
+#[derive(Clone, Debug, Default)]
+struct Affixes {
+    prefix: Option<String>,
+    suffix: Option<String>,
+    bold: bool,
+    italic: bool,
+}
+
+impl Affixes {
+    /// Reads the `prefix`/`suffix`/`font-style`/`font-weight` attributes off a
+    /// node.
+    fn parse(node: &Node) -> Self {
+        Self {
+            prefix: node.attribute("prefix").map(str::to_string),
+            suffix: node.attribute("suffix").map(str::to_string),
+            bold: node.attribute("font-weight") == Some("bold"),
+            italic: node.attribute("font-style") == Some("italic"),
+        }
+    }
+
+    /// Wraps `inner` in the prefix/suffix and inline formatting. An empty
+    /// `inner` is returned untouched so that affixes never render on their own.
+    fn wrap(&self, inner: DisplayString) -> DisplayString {
+        if inner.is_empty() {
+            return inner;
+        }
+
+        let mut res = DisplayString::new();
+        if let Some(prefix) = &self.prefix {
+            res += prefix.as_str();
+        }
+
+        let start = res.len();
+        res += inner;
+        let end = res.len();
+
+        if self.bold {
+            res.formatting.push((start .. end, Formatting::Bold));
+        }
+        if self.italic {
+            res.formatting.push((start .. end, Formatting::Italic));
+        }
+
+        if let Some(suffix) = &self.suffix {
+            res += suffix.as_str();
+        }
+
+        res
+    }
+}
+
+/// A single rendering element of the CSL AST.
+#[derive(Clone, Debug)]
+enum Element {
+    /// `<text>` resolving a variable, a macro or a literal value.
+    Text { source: TextSource, affixes: Affixes },
+    /// `<names>` rendering one or more name variables.
+    Names(Names),
+    /// `<date>` rendering a date variable through its `<date-part>` children.
+    Date(DateElement),
+    /// `<group>` – rendered only if at least one of its variables resolves.
+    Group { children: Vec<Element>, delimiter: Option<String>, affixes: Affixes },
+    /// `<choose>` with its ordered conditional branches.
+    Choose(Vec<Branch>),
+}
+
+/// What a `<text>` element pulls its content from.
+#[derive(Clone, Debug)]
+enum TextSource {
+    Variable(String),
+    Macro(String),
+    Value(String),
+}
+
+/// A `<names>` element and its nested `<name>` options.
+#[derive(Clone, Debug)]
+struct Names {
+    variables: Vec<String>,
+    delimiter: String,
+    name_delimiter: String,
+    et_al_min: Option<usize>,
+    et_al_use_first: usize,
+    initialize_with: Option<String>,
+    affixes: Affixes,
+}
+
+/// A `<date>` element.
+#[derive(Clone, Debug)]
+struct DateElement {
+    variable: String,
+    parts: Vec<DatePart>,
+    affixes: Affixes,
+}
+
+/// A `<date-part>` child of a `<date>`.
+#[derive(Clone, Debug)]
+struct DatePart {
+    name: DatePartName,
+    affixes: Affixes,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum DatePartName {
+    Year,
+    Month,
+    Day,
+}
+
+/// A branch of a `<choose>` element.
+#[derive(Clone, Debug)]
+struct Branch {
+    condition: Condition,
+    children: Vec<Element>,
+}
+
+/// How the individual tests of an `<if>`/`<else-if>` are combined, per the
+/// CSL `match` attribute.
+#[derive(Copy, Clone, Debug)]
+enum MatchMode {
+    /// `match="all"` (the default): every test must hold.
+    All,
+    /// `match="any"`: at least one test must hold.
+    Any,
+    /// `match="none"`: no test may hold.
+    None,
+}
+
+/// The condition guarding a [Branch].
+#[derive(Clone, Debug)]
+enum Condition {
+    /// `<if>`/`<else-if>` matching on variable presence and/or item type.
+    If { variables: Vec<String>, types: Vec<String>, mode: MatchMode },
+    /// `<else>` – always matches.
+    Else,
+}
+
+impl Condition {
+    /// Evaluates the condition against `entry`. Every named variable and type
+    /// contributes one test; the tests are combined according to the `match`
+    /// mode (default `all`). A condition with no tests at all matches.
+    fn matches(&self, entry: &Entry) -> bool {
+        match self {
+            Condition::Else => true,
+            Condition::If { variables, types, mode } => {
+                let tests = variables
+                    .iter()
+                    .map(|v| variable_present(entry, v))
+                    .chain(types.iter().map(|t| t == entry.entry_type()));
+
+                let mut tests = tests.peekable();
+                if tests.peek().is_none() {
+                    return true;
+                }
+
+                match mode {
+                    MatchMode::All => tests.all(|b| b),
+                    MatchMode::Any => tests.any(|b| b),
+                    MatchMode::None => !tests.any(|b| b),
+                }
+            }
+        }
+    }
+}
+
+/// The `<layout>` of a `<citation>` or `<bibliography>`.
+#[derive(Clone, Debug)]
+struct Layout {
+    elements: Vec<Element>,
+    delimiter: Option<String>,
+    affixes: Affixes,
+}
+
+/// A parsed CSL style ready to format entries.
+#[derive(Clone, Debug)]
+pub struct CslStyle {
+    macros: HashMap<String, Vec<Element>>,
+    citation: Layout,
+    /// Optional in CSL 1.0: in-text/note-only styles omit `<bibliography>`.
+    bibliography: Option<Layout>,
+}
+
+impl CslStyle {
+    /// Parses a CSL 1.0 style from its XML source.
+    pub fn parse(xml: &str) -> Result<Self, CslError> {
+        let doc = Document::parse(xml)?;
+        let style = doc.root_element();
+
+        let mut macros = HashMap::new();
+        for node in style.children().filter(|n| n.has_tag_name("macro")) {
+            if let Some(name) = node.attribute("name") {
+                macros.insert(name.to_string(), parse_elements(&node));
+            }
+        }
+
+        let citation = style
+            .children()
+            .find(|n| n.has_tag_name("citation"))
+            .and_then(|n| parse_layout(&n))
+            .ok_or(CslError::MissingElement("citation"))?;
+
+        // `<bibliography>` is optional in CSL 1.0; note/in-text-only styles
+        // legitimately omit it, so its absence is not a parse error.
+        let bibliography = style
+            .children()
+            .find(|n| n.has_tag_name("bibliography"))
+            .and_then(|n| parse_layout(&n));
+
+        Ok(Self { macros, citation, bibliography })
+    }
+
+    /// Renders a layout against `entry`.
+    fn render_layout(&self, layout: &Layout, entry: &Entry) -> DisplayString {
+        let inner = self.render_children(&layout.elements, &layout.delimiter, entry).0;
+        layout.affixes.wrap(inner)
+    }
+
+    /// Renders a list of elements, joining the non-empty results with
+    /// `delimiter`. Returns the combined string and whether any element pulled
+    /// in a resolved variable.
+    fn render_children(
+        &self,
+        elements: &[Element],
+        delimiter: &Option<String>,
+        entry: &Entry,
+    ) -> (DisplayString, bool) {
+        let mut parts = vec![];
+        let mut rendered = false;
+
+        for element in elements {
+            let (string, has_var) = self.render_element(element, entry);
+            rendered |= has_var;
+            if !string.is_empty() {
+                parts.push(string);
+            }
+        }
+
+        (DisplayString::join(&parts, delimiter.as_deref().unwrap_or("")), rendered)
+    }
+
+    /// Renders a single element. The boolean tells whether a variable actually
+    /// resolved, which `<group>` uses to decide suppression.
+    fn render_element(&self, element: &Element, entry: &Entry) -> (DisplayString, bool) {
+        match element {
+            Element::Text { source, affixes } => match source {
+                TextSource::Value(value) => {
+                    (affixes.wrap(value.as_str().into()), false)
+                }
+                TextSource::Variable(var) => match text_variable(entry, var) {
+                    Some(value) => (affixes.wrap(value.as_str().into()), true),
+                    None => (DisplayString::new(), false),
+                },
+                TextSource::Macro(name) => match self.macros.get(name) {
+                    Some(elements) => {
+                        let (inner, rendered) =
+                            self.render_children(elements, &None, entry);
+                        (affixes.wrap(inner), rendered)
+                    }
+                    None => (DisplayString::new(), false),
+                },
+            },
+            Element::Names(names) => self.render_names(names, entry),
+            Element::Date(date) => self.render_date(date, entry),
+            Element::Group { children, delimiter, affixes } => {
+                let (inner, rendered) =
+                    self.render_children(children, delimiter, entry);
+                // A group is suppressed entirely unless one of its variables
+                // resolved.
+                if rendered {
+                    (affixes.wrap(inner), true)
+                } else {
+                    (DisplayString::new(), false)
+                }
+            }
+            Element::Choose(branches) => {
+                for branch in branches {
+                    if branch.condition.matches(entry) {
+                        return self.render_children(&branch.children, &None, entry);
+                    }
+                }
+                (DisplayString::new(), false)
+            }
+        }
+    }
+
+    /// Renders a `<names>` element, honoring the et-al threshold and optional
+    /// initialization.
+    fn render_names(&self, names: &Names, entry: &Entry) -> (DisplayString, bool) {
+        let persons: Vec<&Person> = names
+            .variables
+            .iter()
+            .flat_map(|v| name_variable(entry, v))
+            .flatten()
+            .collect();
+
+        if persons.is_empty() {
+            return (DisplayString::new(), false);
+        }
+
+        let mut rendered: Vec<String> = persons
+            .iter()
+            .map(|p| match &names.initialize_with {
+                Some(with) => {
+                    initialize_given_names(&p.get_given_name_initials_first(true), with)
+                }
+                None => p.get_name_first(true, false),
+            })
+            .collect();
+
+        // Truncate to the et-al cut-off if the style asks for it.
+        if let Some(min) = names.et_al_min {
+            if rendered.len() >= min {
+                rendered.truncate(names.et_al_use_first);
+            }
+        }
+        let truncated = rendered.len() < persons.len();
+
+        let mut joined = rendered.join(&names.name_delimiter);
+        if truncated {
+            joined.push_str(&names.delimiter);
+            joined.push_str("et al.");
+        }
+
+        (names.affixes.wrap(joined.as_str().into()), true)
+    }
+
+    /// Renders a `<date>` element through its `<date-part>` children.
+    fn render_date(&self, date: &DateElement, entry: &Entry) -> (DisplayString, bool) {
+        let value = match date_variable(entry, &date.variable) {
+            Some(date) => date,
+            None => return (DisplayString::new(), false),
+        };
+
+        let mut parts = vec![];
+        for part in &date.parts {
+            let rendered = match part.name {
+                DatePartName::Year => Some(value.year.to_string()),
+                DatePartName::Month => value.month.map(|m| m.to_string()),
+                DatePartName::Day => value.day.map(|d| d.to_string()),
+            };
+
+            if let Some(rendered) = rendered {
+                parts.push(part.affixes.wrap(rendered.as_str().into()));
+            }
+        }
+
+        (date.affixes.wrap(DisplayString::join(&parts, "")), true)
+    }
+}
+
+impl BibliographyFormatter for CslStyle {
+    fn get_reference(&self, entry: &Entry, _prev_entry: Option<&Entry>) -> DisplayString {
+        // Styles without a `<bibliography>` layout have nothing to render here;
+        // fall back to an empty reference rather than failing at parse time.
+        match &self.bibliography {
+            Some(layout) => self.render_layout(layout, entry),
+            None => DisplayString::new(),
+        }
+    }
+}
+
+/// Formats in-text citations by interpreting a [CslStyle]'s `<citation>`
+/// layout against the entries it resolves from the database.
+pub struct CslCitationFormatter<'s> {
+    style: &'s CslStyle,
+    entries: &'s HashMap<String, Entry>,
+}
+
+impl<'s> CslCitationFormatter<'s> {
+    /// Ties a parsed style to the database it should resolve keys against.
+    pub fn new(style: &'s CslStyle, entries: &'s HashMap<String, Entry>) -> Self {
+        Self { style, entries }
+    }
+}
+
+impl<'s> CitationFormatter<'s> for CslCitationFormatter<'s> {
+    fn get_reference(
+        &self,
+        citation: impl Iterator<Item = AtomicCitation<'s>>,
+    ) -> Result<String, CitationError> {
+        let mut items = vec![];
+        for atomic in citation {
+            let entry = self
+                .entries
+                .get(atomic.key)
+                .ok_or_else(|| CitationError::KeyNotFound(atomic.key.to_string()))?;
+
+            let mut rendered =
+                self.style.render_layout(&self.style.citation, entry);
+            if let Some(locator) = &atomic.locator {
+                rendered += ", ";
+                rendered += locator.display().as_str();
+            }
+            items.push(rendered);
+        }
+
+        let delimiter = self.style.citation.delimiter.as_deref().unwrap_or("; ");
+        Ok(DisplayString::join(&items, delimiter).into())
+    }
+}
+
+/// Parses the `<layout>` inside a `<citation>`/`<bibliography>` node.
+fn parse_layout(node: &Node) -> Option<Layout> {
+    let layout = node.children().find(|n| n.has_tag_name("layout"))?;
+    Some(Layout {
+        elements: parse_elements(&layout),
+        delimiter: layout.attribute("delimiter").map(str::to_string),
+        affixes: Affixes::parse(&layout),
+    })
+}
+
+/// Parses every renderable child element of `node` in document order.
+fn parse_elements(node: &Node) -> Vec<Element> {
+    node.children().filter_map(|child| parse_element(&child)).collect()
+}
+
+/// Parses a single element, returning `None` for text nodes and unsupported
+/// tags.
+fn parse_element(node: &Node) -> Option<Element> {
+    if !node.is_element() {
+        return None;
+    }
+
+    match node.tag_name().name() {
+        "text" => {
+            let source = if let Some(var) = node.attribute("variable") {
+                TextSource::Variable(var.to_string())
+            } else if let Some(mac) = node.attribute("macro") {
+                TextSource::Macro(mac.to_string())
+            } else if let Some(value) = node.attribute("value") {
+                TextSource::Value(value.to_string())
+            } else {
+                return None;
+            };
+            Some(Element::Text { source, affixes: Affixes::parse(node) })
+        }
+        "names" => Some(Element::Names(parse_names(node))),
+        "date" => Some(Element::Date(parse_date(node))),
+        "group" => Some(Element::Group {
+            children: parse_elements(node),
+            delimiter: node.attribute("delimiter").map(str::to_string),
+            affixes: Affixes::parse(node),
+        }),
+        "choose" => Some(Element::Choose(parse_choose(node))),
+        _ => None,
+    }
+}
+
+fn parse_names(node: &Node) -> Names {
+    let name = node.children().find(|n| n.has_tag_name("name"));
+
+    Names {
+        variables: split_tokens(node.attribute("variable")),
+        delimiter: node.attribute("delimiter").unwrap_or(", ").to_string(),
+        name_delimiter: name
+            .and_then(|n| n.attribute("delimiter"))
+            .unwrap_or(", ")
+            .to_string(),
+        et_al_min: name
+            .and_then(|n| n.attribute("et-al-min"))
+            .and_then(|v| v.parse().ok()),
+        et_al_use_first: name
+            .and_then(|n| n.attribute("et-al-use-first"))
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1),
+        initialize_with: name
+            .and_then(|n| n.attribute("initialize-with"))
+            .map(str::to_string),
+        affixes: Affixes::parse(node),
+    }
+}
+
+/// Re-delimits the initials of an initials-first name (`"J. R. R. Tolkien"`)
+/// using the style's `initialize-with` string, so `"."`, `". "` and `""` all
+/// produce the delimiter the style actually asked for instead of whatever
+/// [Person::get_given_name_initials_first](crate::types::Person) hardcodes. The
+/// leading run of single-letter tokens is treated as the initials; the rest is
+/// the family name.
+fn initialize_given_names(name: &str, with: &str) -> String {
+    let tokens: Vec<&str> = name.split_whitespace().collect();
+    let split = tokens
+        .iter()
+        .position(|t| t.trim_end_matches('.').chars().count() != 1)
+        .unwrap_or(tokens.len());
+
+    let initials: String = tokens[.. split]
+        .iter()
+        .map(|t| format!("{}{}", t.trim_end_matches('.'), with))
+        .collect();
+    let family = tokens[split ..].join(" ");
+
+    match (initials.is_empty(), family.is_empty()) {
+        (false, false) if initials.ends_with(' ') => format!("{}{}", initials, family),
+        (false, false) => format!("{} {}", initials, family),
+        (false, true) => initials.trim_end().to_string(),
+        (true, _) => family,
+    }
+}
+
+fn parse_date(node: &Node) -> DateElement {
+    let parts = node
+        .children()
+        .filter(|n| n.has_tag_name("date-part"))
+        .filter_map(|part| {
+            let name = match part.attribute("name") {
+                Some("year") => DatePartName::Year,
+                Some("month") => DatePartName::Month,
+                Some("day") => DatePartName::Day,
+                _ => return None,
+            };
+            Some(DatePart { name, affixes: Affixes::parse(&part) })
+        })
+        .collect();
+
+    DateElement {
+        variable: node.attribute("variable").unwrap_or("issued").to_string(),
+        parts,
+        affixes: Affixes::parse(node),
+    }
+}
+
+fn parse_choose(node: &Node) -> Vec<Branch> {
+    let mut branches = vec![];
+    for child in node.children().filter(|n| n.is_element()) {
+        let condition = match child.tag_name().name() {
+            "if" | "else-if" => Condition::If {
+                variables: split_tokens(child.attribute("variable")),
+                types: split_tokens(child.attribute("type")),
+                mode: match child.attribute("match") {
+                    Some("any") => MatchMode::Any,
+                    Some("none") => MatchMode::None,
+                    _ => MatchMode::All,
+                },
+            },
+            "else" => Condition::Else,
+            _ => continue,
+        };
+        branches.push(Branch { condition, children: parse_elements(&child) });
+    }
+    branches
+}
+
+/// Splits a space-separated attribute (as used by `variable`/`type`) into its
+/// tokens.
+fn split_tokens(attr: Option<&str>) -> Vec<String> {
+    attr.map(|s| s.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Whether a CSL variable of any kind resolves for `entry`.
+fn variable_present(entry: &Entry, var: &str) -> bool {
+    text_variable(entry, var).is_some()
+        || name_variable(entry, var).is_some()
+        || date_variable(entry, var).is_some()
+}
+
+/// Resolves a CSL standard (textual) variable to a plain string.
+fn text_variable(entry: &Entry, var: &str) -> Option<String> {
+    match var {
+        "title" => entry.get_title().map(|t| t.to_string()),
+        "container-title" => entry.get_container_title().map(|t| t.to_string()),
+        "publisher" => entry.get_publisher().map(str::to_string),
+        "publisher-place" => entry.get_location().map(str::to_string),
+        "URL" => entry.get_url().map(str::to_string),
+        "DOI" => entry.get_doi().map(str::to_string),
+        "page" => entry.get_page_range().map(|r| format!("{}–{}", r.start, r.end)),
+        _ => None,
+    }
+}
+
+/// Resolves a CSL name variable to its list of people.
+fn name_variable<'e>(entry: &'e Entry, var: &str) -> Option<&'e [Person]> {
+    match var {
+        "author" => Some(entry.get_authors()),
+        "editor" => entry.get_editors(),
+        "translator" => entry.get_translators(),
+        _ => None,
+    }
+}
+
+/// Resolves a CSL date variable.
+fn date_variable<'e>(entry: &'e Entry, var: &str) -> Option<&'e Date> {
+    match var {
+        "issued" => entry.get_date(),
+        "accessed" => entry.get_access_date(),
+        _ => None,
+    }
+}
@@ -2,13 +2,14 @@
 
 use super::types::Person;
 use super::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::Into;
 use std::ops::{Add, AddAssign};
 use thiserror::Error;
 
 pub mod apa;
 pub mod chicago;
+pub mod csl;
 pub mod ieee;
 pub mod mla;
 
@@ -43,15 +44,77 @@ pub trait BibliographyFormatter {
     fn get_reference(&self, entry: &Entry, prev_entry: Option<&Entry>) -> DisplayString;
 }
 
+/// A typed pointer to a location within a cited work. The variant selects the
+/// label a style renders ("p."/"pp.", "chap.", "fig.", …) while the carried
+/// value decides between the singular and plural form.
+#[derive(Clone, Debug)]
+pub enum Locator<'s> {
+    /// A page or page range.
+    Page(&'s str),
+    /// A chapter.
+    Chapter(&'s str),
+    /// A figure.
+    Figure(&'s str),
+    /// A section.
+    Section(&'s str),
+    /// A verse or verse range.
+    Verse(&'s str),
+    /// A note.
+    Note(&'s str),
+}
+
+impl<'s> Locator<'s> {
+    /// The referenced value, e.g. `"5"` or `"12–14"`.
+    pub fn value(&self) -> &'s str {
+        match self {
+            Locator::Page(v)
+            | Locator::Chapter(v)
+            | Locator::Figure(v)
+            | Locator::Section(v)
+            | Locator::Verse(v)
+            | Locator::Note(v) => v,
+        }
+    }
+
+    /// The singular and plural label for this locator kind.
+    fn labels(&self) -> (&'static str, &'static str) {
+        match self {
+            Locator::Page(_) => ("p.", "pp."),
+            Locator::Chapter(_) => ("chap.", "chaps."),
+            Locator::Figure(_) => ("fig.", "figs."),
+            Locator::Section(_) => ("sec.", "secs."),
+            Locator::Verse(_) => ("v.", "vv."),
+            Locator::Note(_) => ("n.", "nn."),
+        }
+    }
+
+    /// Renders the locator as its label followed by the value, delegating the
+    /// singular/plural decision to [format_range] so it is made the same way
+    /// everywhere. The value is routed through [split_locator_range] first so
+    /// that a hyphenated identifier (e.g. a section id like `A-1`) is not
+    /// mistaken for a numeric range.
+    pub fn display(&self) -> String {
+        let (prefix_s, prefix_m) = self.labels();
+        let value = self.value();
+        match split_locator_range(value) {
+            Some((start, end)) => format_range(prefix_s, prefix_m, &(start .. end)),
+            None => format_range(prefix_s, prefix_m, &(value .. value)),
+        }
+    }
+}
+
 /// Represents a citation of one or more database entries.
 #[derive(Clone, Debug)]
 pub struct AtomicCitation<'s> {
     /// Cited entry keys.
     pub key: &'s str,
-    /// Supplements for each entry key such as page or chapter number.
-    pub supplement: Option<&'s str>,
+    /// An optional typed locator such as a page or chapter number.
+    pub locator: Option<Locator<'s>>,
     /// Assigned number of the citation.
     pub number: Option<usize>,
+    /// Suppress the author, e.g. for narrative citations where the author
+    /// already appears in running text ("Smith (2020)" → "(2020)").
+    pub suppress_author: bool,
 }
 
 /// Structs implementing this trait can generate the appropriate reference
@@ -82,8 +145,8 @@ impl<'s> CitationFormatter<'s> for KeyCitationFormatter<'s> {
                 return Err(CitationError::KeyNotFound(atomic.key.to_string()));
             }
 
-            items.push(if let Some(supplement) = atomic.supplement {
-                format!("{} ({})", atomic.key, supplement)
+            items.push(if let Some(locator) = &atomic.locator {
+                format!("{} ({})", atomic.key, locator.display())
             } else {
                 atomic.key.to_string()
             });
@@ -112,14 +175,14 @@ impl<'s> CitationFormatter<'s> for NumericalCitationFormatter<'s> {
             let number = atomic
                 .number
                 .ok_or_else(|| CitationError::NoNumber(atomic.key.to_string()))?;
-            ids.push((number, atomic.supplement));
+            ids.push((number, atomic.locator.as_ref().map(Locator::display)));
         }
 
         ids.sort_by(|(a, _), (b, _)| a.cmp(&b));
 
-        enum CiteElement<'a> {
+        enum CiteElement {
             Range(std::ops::Range<usize>),
-            Single((usize, Option<&'a str>)),
+            Single((usize, Option<String>)),
         }
 
         let mut res_elems = vec![];
@@ -137,9 +200,6 @@ impl<'s> CitationFormatter<'s> for NumericalCitationFormatter<'s> {
                     r.end = number;
                     res_elems.push(CiteElement::Range(r));
                 }
-                _ if supplement.is_some() => {
-                    res_elems.push(CiteElement::Single((number, supplement)));
-                }
                 _ => {
                     res_elems.push(CiteElement::Range(number .. number));
                 }
@@ -168,6 +228,430 @@ impl<'s> CitationFormatter<'s> for NumericalCitationFormatter<'s> {
     }
 }
 
+/// Like [CitationFormatter], but handed the whole ordered stream of citations
+/// up front. Seeing the full stream is what makes correct author-date and
+/// Chicago-notes output possible: year-collision disambiguation and
+/// Ibid./short-form collapsing both need context a single-citation formatter
+/// cannot observe. One [DisplayString] marker is returned per input citation.
+pub trait StatefulCitationFormatter<'s> {
+    /// Render every citation in `citations`, in order.
+    fn get_references(
+        &self,
+        citations: &[AtomicCitation<'s>],
+    ) -> Result<Vec<DisplayString>, CitationError>;
+}
+
+/// The primary-author collision key of an entry: the first author's name as it
+/// is rendered in a name list.
+fn primary_author_key(entry: &Entry) -> Option<String> {
+    entry.get_authors().first().map(|p| p.get_name_first(true, false))
+}
+
+/// The publication year of an entry, if dated.
+fn entry_year(entry: &Entry) -> Option<i32> {
+    entry.get_date().map(|d| d.year)
+}
+
+/// Appends a typed locator to a citation marker, if present.
+fn append_locator(target: &mut DisplayString, locator: &Option<Locator>) {
+    if let Some(locator) = locator {
+        *target += ", ";
+        *target += locator.display().as_str();
+    }
+}
+
+/// Author-date formatter that appends disambiguating letters to entries that
+/// share a leading author and year ("2020a", "2020b").
+pub struct AuthorDateCitationFormatter<'s> {
+    entries: &'s HashMap<String, Entry>,
+}
+
+impl<'s> AuthorDateCitationFormatter<'s> {
+    /// Creates a formatter over `entries`.
+    pub fn new(entries: &'s HashMap<String, Entry>) -> Self {
+        Self { entries }
+    }
+
+    /// Orders two entry keys by bibliographic sort order — primary author, then
+    /// year, then title — which is the order CSL assigns disambiguation letters
+    /// in. Citation keys themselves (`smith2020`, arbitrary user ids) are never
+    /// used for ordering.
+    fn bibliographic_order(&self, a: &str, b: &str) -> std::cmp::Ordering {
+        let ea = &self.entries[a];
+        let eb = &self.entries[b];
+        primary_author_key(ea)
+            .cmp(&primary_author_key(eb))
+            .then_with(|| entry_year(ea).cmp(&entry_year(eb)))
+            .then_with(|| {
+                ea.get_title()
+                    .map(|t| t.to_string())
+                    .cmp(&eb.get_title().map(|t| t.to_string()))
+            })
+    }
+
+    /// Builds the map from entry key to disambiguation suffix. Entries sharing a
+    /// `(primary author, year)` get lettered suffixes (`a`, `b`, …) assigned in
+    /// bibliographic sort order; unique entries get no suffix. Sorting by
+    /// (author, year, title) leaves colliding entries contiguous, so the letters
+    /// fall out in bibliography order directly.
+    fn disambiguation_suffixes(
+        &self,
+        keys: &[&'s str],
+    ) -> HashMap<String, String> {
+        let mut unique: Vec<&str> = keys.to_vec();
+        unique.sort_by(|a, b| self.bibliographic_order(a, b));
+        unique.dedup();
+
+        let group_key = |k: &str| {
+            let entry = &self.entries[k];
+            (primary_author_key(entry), entry_year(entry))
+        };
+
+        let mut suffixes = HashMap::new();
+        let mut i = 0;
+        while i < unique.len() {
+            let key = group_key(unique[i]);
+            let mut j = i;
+            while j < unique.len() && group_key(unique[j]) == key {
+                j += 1;
+            }
+
+            if j - i >= 2 {
+                for (n, entry_key) in unique[i .. j].iter().enumerate() {
+                    suffixes.insert(entry_key.to_string(), suffix_letters(n));
+                }
+            }
+
+            i = j;
+        }
+
+        suffixes
+    }
+
+    /// Renders a bibliography for `keys` in bibliographic sort order, applying
+    /// the same disambiguation suffixes as the in-text markers so a collision
+    /// prints `2020a`/`2020b` consistently on both sides. Each reference is
+    /// rendered through `formatter` and the suffix spliced in right after the
+    /// year.
+    pub fn get_bibliography<B: BibliographyFormatter>(
+        &self,
+        formatter: &B,
+        keys: &[&'s str],
+    ) -> Result<Vec<DisplayString>, CitationError> {
+        for key in keys {
+            if !self.entries.contains_key(*key) {
+                return Err(CitationError::KeyNotFound(key.to_string()));
+            }
+        }
+
+        let suffixes = self.disambiguation_suffixes(keys);
+
+        let mut unique: Vec<&str> = keys.to_vec();
+        unique.sort_by(|a, b| self.bibliographic_order(a, b));
+        unique.dedup();
+
+        let mut res = vec![];
+        let mut prev: Option<&Entry> = None;
+        for key in &unique {
+            let entry = &self.entries[*key];
+            let mut reference = formatter.get_reference(entry, prev);
+            if let (Some(suffix), Some(year)) =
+                (suffixes.get(*key), entry_year(entry))
+            {
+                splice_year_suffix(&mut reference, year, suffix);
+            }
+            res.push(reference);
+            prev = Some(entry);
+        }
+
+        Ok(res)
+    }
+}
+
+/// Renders a zero-based disambiguation index as a lowercase letter suffix,
+/// continuing with `aa`, `ab`, … past the 26th entry so large collision groups
+/// never overflow into non-letter code points.
+fn suffix_letters(mut n: usize) -> String {
+    let mut s = String::new();
+    loop {
+        s.insert(0, (b'a' + (n % 26) as u8) as char);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+    s
+}
+
+/// Splices `suffix` into `reference` immediately after the first rendering of
+/// `year`, shifting any formatting ranges that follow, so the bibliography's
+/// disambiguation letter stays attached to the year the same way the in-text
+/// marker is. Falls back to appending if the year is not present.
+fn splice_year_suffix(reference: &mut DisplayString, year: i32, suffix: &str) {
+    let needle = year.to_string();
+    if let Some(pos) = reference.value.find(&needle) {
+        let at = pos + needle.len();
+        reference.value.insert_str(at, suffix);
+        let shift = suffix.len();
+        for (range, _) in reference.formatting.iter_mut() {
+            if range.start >= at {
+                range.start += shift;
+            }
+            if range.end >= at {
+                range.end += shift;
+            }
+        }
+    } else {
+        *reference += suffix;
+    }
+}
+
+impl<'s> StatefulCitationFormatter<'s> for AuthorDateCitationFormatter<'s> {
+    fn get_references(
+        &self,
+        citations: &[AtomicCitation<'s>],
+    ) -> Result<Vec<DisplayString>, CitationError> {
+        for atomic in citations {
+            if !self.entries.contains_key(atomic.key) {
+                return Err(CitationError::KeyNotFound(atomic.key.to_string()));
+            }
+        }
+
+        let keys: Vec<&str> = citations.iter().map(|c| c.key).collect();
+        let suffixes = self.disambiguation_suffixes(&keys);
+
+        let mut res = vec![];
+        for atomic in citations {
+            let entry = &self.entries[atomic.key];
+
+            let mut marker = DisplayString::from_str("(");
+            if !atomic.suppress_author {
+                if let Some(author) = primary_author_key(entry) {
+                    marker += author.as_str();
+                    marker += " ";
+                }
+            }
+            if let Some(year) = entry_year(entry) {
+                marker += year.to_string().as_str();
+            }
+            if let Some(suffix) = suffixes.get(atomic.key) {
+                marker += suffix.as_str();
+            }
+            append_locator(&mut marker, &atomic.locator);
+            marker += ")";
+
+            res.push(marker);
+        }
+
+        Ok(res)
+    }
+}
+
+/// Chicago-notes style formatter that collapses repeated references into
+/// "Ibid." (for an immediately preceding cite of the same key) or a shortened
+/// "Author, *Title*" form (for a key cited earlier but not just prior). The
+/// shortened form drops the year; the title itself is not abbreviated.
+pub struct NotesCitationFormatter<'s> {
+    entries: &'s HashMap<String, Entry>,
+}
+
+impl<'s> NotesCitationFormatter<'s> {
+    /// Creates a formatter over `entries`.
+    pub fn new(entries: &'s HashMap<String, Entry>) -> Self {
+        Self { entries }
+    }
+
+    /// Renders a full or shortened note: "Author, *Title*[, Year]". The short
+    /// form drops the year; both keep the title's italics.
+    fn note_form(&self, entry: &Entry, short: bool) -> DisplayString {
+        let mut note = DisplayString::new();
+        if let Some(author) = primary_author_key(entry) {
+            note += author.as_str();
+            note += ", ";
+        }
+        if let Some(title) = entry.get_title() {
+            note.start_format(Formatting::Italic);
+            note += title.to_string().as_str();
+            note.commit_formats();
+        }
+        if !short {
+            if let Some(year) = entry_year(entry) {
+                note += ", ";
+                note += year.to_string().as_str();
+            }
+        }
+        note
+    }
+}
+
+impl<'s> StatefulCitationFormatter<'s> for NotesCitationFormatter<'s> {
+    fn get_references(
+        &self,
+        citations: &[AtomicCitation<'s>],
+    ) -> Result<Vec<DisplayString>, CitationError> {
+        for atomic in citations {
+            if !self.entries.contains_key(atomic.key) {
+                return Err(CitationError::KeyNotFound(atomic.key.to_string()));
+            }
+        }
+
+        let mut res = vec![];
+        let mut seen = HashSet::new();
+        let mut prev: Option<&str> = None;
+
+        for atomic in citations {
+            let entry = &self.entries[atomic.key];
+
+            let mut note = if prev == Some(atomic.key) {
+                DisplayString::from_str("Ibid.")
+            } else {
+                self.note_form(entry, seen.contains(atomic.key))
+            };
+            append_locator(&mut note, &atomic.locator);
+
+            seen.insert(atomic.key);
+            prev = Some(atomic.key);
+            res.push(note);
+        }
+
+        Ok(res)
+    }
+}
+
+/// Selects how [CitationManager] renders its in-text footnote markers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MarkerStyle {
+    /// A superscript number set where the citation appears, e.g. `¹²`.
+    Superscript,
+    /// A markdown footnote reference such as `[^1]`, paired with a `[^1]: …`
+    /// definition in the note list.
+    Markdown,
+}
+
+/// Owns the running sequence of footnote/endnote citations for a document.
+///
+/// Following the markdown definition/reference split, a single streaming pass
+/// calls [cite](CitationManager::cite) at every citation site to obtain the
+/// in-text marker, assigning numbers in first-appearance order and reusing the
+/// number for repeat cites of the same key. Once the pass is finished,
+/// [notes](CitationManager::notes) returns the collected note list, each entry
+/// rendered through the [BibliographyFormatter].
+pub struct CitationManager<'a, B: BibliographyFormatter> {
+    formatter: &'a B,
+    entries: &'a HashMap<String, Entry>,
+    style: MarkerStyle,
+    /// Cited keys in first-appearance order; the index is the note number − 1.
+    order: Vec<String>,
+    numbers: HashMap<String, usize>,
+}
+
+impl<'a, B: BibliographyFormatter> CitationManager<'a, B> {
+    /// Starts a new manager over `entries`, rendering notes with `formatter`.
+    pub fn new(
+        formatter: &'a B,
+        entries: &'a HashMap<String, Entry>,
+        style: MarkerStyle,
+    ) -> Self {
+        Self {
+            formatter,
+            entries,
+            style,
+            order: vec![],
+            numbers: HashMap::new(),
+        }
+    }
+
+    /// Records a citation of `key` and returns the in-text marker to splice
+    /// where it appears. Repeat cites of the same key reuse their number.
+    pub fn cite(&mut self, key: &str) -> Result<String, CitationError> {
+        if !self.entries.contains_key(key) {
+            return Err(CitationError::KeyNotFound(key.to_string()));
+        }
+
+        let number = match self.numbers.get(key) {
+            Some(number) => *number,
+            None => {
+                let number = self.order.len() + 1;
+                self.order.push(key.to_string());
+                self.numbers.insert(key.to_string(), number);
+                number
+            }
+        };
+
+        Ok(self.marker(number))
+    }
+
+    /// Finalizes the pass into the ordered note list. Each note is prefixed
+    /// with its anchor and rendered via the [BibliographyFormatter].
+    pub fn notes(&self) -> Vec<DisplayString> {
+        let mut notes = vec![];
+        let mut prev = None;
+
+        for (i, key) in self.order.iter().enumerate() {
+            let entry = &self.entries[key];
+
+            let mut note = DisplayString::new();
+            note += match self.style {
+                MarkerStyle::Markdown => format!("[^{}]: ", i + 1),
+                MarkerStyle::Superscript => format!("{}. ", i + 1),
+            }
+            .as_str();
+            note += self.formatter.get_reference(entry, prev);
+
+            notes.push(note);
+            prev = Some(entry);
+        }
+
+        notes
+    }
+
+    /// Renders the in-text marker for a given note number.
+    fn marker(&self, number: usize) -> String {
+        match self.style {
+            MarkerStyle::Markdown => format!("[^{}]", number),
+            MarkerStyle::Superscript => number
+                .to_string()
+                .chars()
+                .map(superscript_digit)
+                .collect(),
+        }
+    }
+}
+
+/// Maps an ASCII digit to its Unicode superscript counterpart.
+fn superscript_digit(c: char) -> char {
+    match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        _ => c,
+    }
+}
+
+/// Splits a locator value into its endpoints when it denotes a range. An
+/// en-dash always separates a range; an ASCII hyphen only does so when both
+/// sides parse as integers, so hyphenated identifiers are left intact.
+fn split_locator_range(value: &str) -> Option<(&str, &str)> {
+    if let Some((start, end)) = value.split_once('–') {
+        return Some((start.trim(), end.trim()));
+    }
+
+    if let Some((start, end)) = value.split_once('-') {
+        if start.trim().parse::<i64>().is_ok() && end.trim().parse::<i64>().is_ok() {
+            return Some((start.trim(), end.trim()));
+        }
+    }
+
+    None
+}
+
 fn format_range<T: std::fmt::Display + PartialEq>(
     prefix_s: &str,
     prefix_m: &str,
@@ -393,45 +877,149 @@ impl DisplayString {
         res
     }
 
-    /// Applies the formatting as ANSI / VT100 control sequences and
-    /// prints that formatted string to standard output.
-    pub fn print_ansi_vt100(&self) -> String {
-        let mut start_end = vec![];
+    /// Walks the `formatting` ranges and interleaves the markers supplied by
+    /// `target` around the matching slices of `value`, escaping every text
+    /// segment that falls outside an inserted marker. Opening markers are
+    /// emitted in ascending order of their range start and closing markers in
+    /// LIFO order, which keeps *nested* ranges well-formed. Genuinely
+    /// overlapping, non-nested ranges (e.g. bold `0..3` and italic `1..5`) are
+    /// not split at their crossing point and would produce mis-nested markup —
+    /// but `DisplayString` construction never yields such ranges, so this stays
+    /// latent.
+    fn render(&self, target: Target) -> String {
+        // Collect the open and close boundaries as separate events so that we
+        // can sort them independently – opens ascending, closes LIFO.
+        // A close marker remembers where its range opened so that closes can
+        // be ordered LIFO against the opens.
+        enum Marker {
+            Open(Formatting),
+            Close(Formatting, usize),
+        }
 
-        for item in &self.formatting {
-            let opt = item.1;
-            if opt == Formatting::NoHyphenation {
+        let mut events: Vec<(usize, usize, Marker)> = vec![];
+        for (i, (range, f)) in self.formatting.iter().enumerate() {
+            if target.marker(*f, false).is_none() {
                 continue;
             }
-            let min = item.0.start;
-            let max = item.0.end;
-
-            start_end.push((opt.clone(), min, false));
-            start_end.push((opt, max, true));
+            events.push((range.start, i, Marker::Open(*f)));
+            events.push((range.end, i, Marker::Close(*f, range.start)));
         }
 
-        start_end.sort_by(|a, b| a.1.cmp(&b.1).reverse());
+        // Order the events: by index first, closing before opening at the same
+        // index. Among closes at one index the range that opened last closes
+        // first (LIFO); among opens the range that starts first opens first.
+        // This is well-formed for nested ranges only.
+        events.sort_by(|a, b| {
+            a.0.cmp(&b.0).then_with(|| match (&a.2, &b.2) {
+                (Marker::Close(..), Marker::Open(_)) => std::cmp::Ordering::Less,
+                (Marker::Open(_), Marker::Close(..)) => std::cmp::Ordering::Greater,
+                (Marker::Close(_, sa), Marker::Close(_, sb)) => {
+                    sb.cmp(sa).then_with(|| b.1.cmp(&a.1))
+                }
+                (Marker::Open(_), Marker::Open(_)) => a.1.cmp(&b.1),
+            })
+        });
 
         let mut res = String::new();
-        let mut pointer = self.len();
+        let mut pointer = 0;
 
-        for (f, index, end) in &start_end {
-            res = (&self.value[*index .. pointer]).to_string() + &res;
+        for (index, _, marker) in &events {
+            res += &target.escape(&self.value[pointer .. *index]);
             pointer = *index;
 
-            let code = if *end {
-                "0"
-            } else {
-                match f {
-                    Formatting::Bold => "1",
-                    Formatting::Italic => "3",
-                    Formatting::NoHyphenation => unreachable!(),
-                }
+            let tag = match marker {
+                Marker::Open(f) => target.marker(*f, false),
+                Marker::Close(f, _) => target.marker(*f, true),
             };
-            res = format!("\x1b[{}m", code) + &res;
+            if let Some(tag) = tag {
+                res += &tag;
+            }
         }
-        res = (&self.value[0 .. pointer]).to_string() + &res;
 
+        res += &target.escape(&self.value[pointer ..]);
         res
     }
+
+    /// Applies the formatting as ANSI / VT100 control sequences and
+    /// returns that formatted string.
+    pub fn print_ansi_vt100(&self) -> String {
+        self.render(Target::Ansi)
+    }
+
+    /// Renders the string as HTML, wrapping the formatted ranges in the
+    /// matching inline markup and escaping `&`, `<`, `>`, and `"` in the
+    /// surrounding text so the result is safe to splice into a document.
+    pub fn to_html(&self) -> String {
+        self.render(Target::Html)
+    }
+}
+
+/// A rendering target for [DisplayString::render]. Each variant knows how to
+/// turn a [Formatting] into its opening and closing markers and how to escape
+/// the literal text between them.
+#[derive(Copy, Clone, Debug)]
+enum Target {
+    /// ANSI / VT100 control sequences for terminal output.
+    Ansi,
+    /// Inline HTML markup.
+    Html,
+}
+
+impl Target {
+    /// Returns the marker for `f`, or `None` if the target does not render it.
+    /// `close` selects the closing marker over the opening one.
+    fn marker(&self, f: Formatting, close: bool) -> Option<String> {
+        match self {
+            Target::Ansi => {
+                if f == Formatting::NoHyphenation {
+                    return None;
+                }
+                let code = if close {
+                    "0"
+                } else {
+                    match f {
+                        Formatting::Bold => "1",
+                        Formatting::Italic => "3",
+                        Formatting::NoHyphenation => unreachable!(),
+                    }
+                };
+                Some(format!("\x1b[{}m", code))
+            }
+            Target::Html => Some(
+                match (f, close) {
+                    (Formatting::Bold, false) => "<strong>",
+                    (Formatting::Bold, true) => "</strong>",
+                    (Formatting::Italic, false) => "<em>",
+                    (Formatting::Italic, true) => "</em>",
+                    (Formatting::NoHyphenation, false) => {
+                        "<span style=\"white-space:nowrap\">"
+                    }
+                    (Formatting::NoHyphenation, true) => "</span>",
+                }
+                .to_string(),
+            ),
+        }
+    }
+
+    /// Escapes the literal text `s` for this target. The ANSI target passes the
+    /// text through unchanged; the HTML target replaces the markup-significant
+    /// characters with their entities.
+    fn escape(&self, s: &str) -> String {
+        match self {
+            Target::Ansi => s.to_string(),
+            Target::Html => {
+                let mut res = String::with_capacity(s.len());
+                for c in s.chars() {
+                    match c {
+                        '&' => res.push_str("&amp;"),
+                        '<' => res.push_str("&lt;"),
+                        '>' => res.push_str("&gt;"),
+                        '"' => res.push_str("&quot;"),
+                        _ => res.push(c),
+                    }
+                }
+                res
+            }
+        }
+    }
 }